@@ -0,0 +1,60 @@
+/*
+ *
+ *    Copyright (c) 2020-2022 Project CHIP Authors
+ *
+ *    Licensed under the Apache License, Version 2.0 (the "License");
+ *    you may not use this file except in compliance with the License.
+ *    You may obtain a copy of the License at
+ *
+ *        http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *    Unless required by applicable law or agreed to in writing, software
+ *    distributed under the License is distributed on an "AS IS" BASIS,
+ *    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *    See the License for the specific language governing permissions and
+ *    limitations under the License.
+ */
+
+//! The endpoint/node composition data model.
+//!
+//! This only covers the `Endpoint`/`Node`/`DeviceType` shapes needed by the
+//! Descriptor cluster (`data_model::system_model::descriptor`). The rest of
+//! the cluster/attribute framework (`Cluster`, `Attribute`, `Access`,
+//! `Quality`, `Handler`, `Dataver`, etc.) is pre-existing, crate-wide
+//! infrastructure shared by every cluster and is not part of this module.
+
+/// Endpoint identifier, unique within a `Node`.
+pub type EndptId = u16;
+
+/// A Matter device type, as advertised in the Descriptor cluster's
+/// DeviceTypeList attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceType {
+    pub dtype: u32,
+    pub drev: u16,
+}
+
+/// A single endpoint within a `Node`, exposing a set of clusters.
+pub struct Endpoint<'a> {
+    pub id: EndptId,
+    pub clusters: &'a [Cluster<'a>],
+    /// All device types advertised by this endpoint (a primary type plus
+    /// any additional namespace/semantic-helper types).
+    pub device_types: &'a [DeviceType],
+    /// Semantic tags used to disambiguate this endpoint from otherwise
+    /// identical endpoints (Descriptor cluster TagList feature).
+    pub tag_list: &'a [crate::data_model::system_model::descriptor::Tag<'a>],
+    /// Cluster IDs this endpoint consumes as a client/initiator (e.g. via
+    /// the Binding cluster), reported in the Descriptor cluster's
+    /// ClientList attribute.
+    pub client_clusters: &'a [u32],
+    /// The endpoint this one is composed under, for tree-style composition
+    /// (e.g. a bridge's bridged-device endpoints).
+    pub parent: Option<EndptId>,
+}
+
+/// The full set of endpoints making up a Matter node.
+pub struct Node<'a> {
+    pub id: u16,
+    pub endpoints: &'a [Endpoint<'a>],
+}
@@ -22,11 +22,15 @@ use strum::FromRepr;
 use crate::attribute_enum;
 use crate::data_model::objects::*;
 use crate::error::Error;
-use crate::tlv::{TLVWriter, TagType, ToTLV};
+use crate::tlv::{Nullable, TLVWriter, TagType, ToTLV};
 use crate::utils::rand::Rand;
 
 pub const ID: u32 = 0x001D;
 
+/// Bit 0 of the cluster's FeatureMap: the endpoint supports the TagList
+/// feature (set only for endpoints with a non-empty tag list).
+const TAG_LIST_FEATURE: u32 = 0x01;
+
 #[derive(FromRepr)]
 #[repr(u16)]
 #[allow(clippy::enum_variant_names)]
@@ -35,12 +39,29 @@ pub enum Attributes {
     ServerList = 1,
     ClientList = 2,
     PartsList = 3,
+    TagList = 4,
 }
 
 attribute_enum!(Attributes);
 
+/// A semantic tag used to disambiguate otherwise-identical endpoints, as
+/// defined by the Descriptor cluster's TagList feature.
+#[derive(Clone, Copy)]
+pub struct Tag<'a> {
+    pub mfg_code: Nullable<u16>,
+    pub namespace_id: u8,
+    pub tag: u8,
+    // The spec models Label as optional *and* nullable; we collapse both to
+    // "no label" here since no caller in this crate needs to tell them apart.
+    pub label: Option<&'a str>,
+}
+
 pub const CLUSTER: Cluster<'static> = Cluster {
     id: ID as _,
+    // The TagList feature is only advertised per-endpoint, for endpoints
+    // that actually have a tag list (see `DescriptorCluster::read`), so the
+    // static feature map that backs the generic system-attribute dispatch
+    // below never claims it.
     feature_map: 0,
     attributes: &[
         FEATURE_MAP,
@@ -49,14 +70,72 @@ pub const CLUSTER: Cluster<'static> = Cluster {
         Attribute::new(Attributes::ServerList as u16, Access::RV, Quality::NONE),
         Attribute::new(Attributes::PartsList as u16, Access::RV, Quality::NONE),
         Attribute::new(Attributes::ClientList as u16, Access::RV, Quality::NONE),
+        Attribute::new(Attributes::TagList as u16, Access::RV, Quality::NONE),
     ],
     commands: &[],
 };
 
+/// The device types advertised by `endpoint_id`, or an empty slice if no
+/// endpoint with that ID exists in `node`.
+fn device_types_for<'n>(node: &'n Node, endpoint_id: u16) -> &'n [DeviceType] {
+    node.endpoints
+        .iter()
+        .find(|endpoint| endpoint.id == endpoint_id)
+        .map(|endpoint| endpoint.device_types)
+        .unwrap_or(&[])
+}
+
+/// The FeatureMap advertised for `endpoint_id` specifically: the base
+/// cluster feature map plus the TagList bit when that endpoint actually has
+/// a non-empty tag list.
+fn feature_map_for(node: &Node, endpoint_id: u16) -> u32 {
+    let mut feature_map = CLUSTER.feature_map;
+    for endpoint in node.endpoints {
+        if endpoint.id == endpoint_id && !endpoint.tag_list.is_empty() {
+            feature_map |= TAG_LIST_FEATURE;
+        }
+    }
+
+    feature_map
+}
+
+/// The attribute IDs advertised for `endpoint_id` specifically: the
+/// cluster's full attribute list, minus TagList for endpoints that don't
+/// carry a tag list.
+fn attribute_list_for(node: &Node, endpoint_id: u16) -> impl Iterator<Item = u16> + '_ {
+    let has_tag_list = feature_map_for(node, endpoint_id) & TAG_LIST_FEATURE != 0;
+
+    CLUSTER
+        .attributes
+        .iter()
+        .map(|attr| attr.id)
+        .filter(move |&id| has_tag_list || id != Attributes::TagList as u16)
+}
+
+/// The semantic tags advertised by `endpoint_id`, or an empty slice if no
+/// endpoint with that ID exists in `node`.
+fn tag_list_for<'n>(node: &'n Node, endpoint_id: u16) -> &'n [Tag<'n>] {
+    node.endpoints
+        .iter()
+        .find(|endpoint| endpoint.id == endpoint_id)
+        .map(|endpoint| endpoint.tag_list)
+        .unwrap_or(&[])
+}
+
+/// The client/binding cluster IDs advertised by `endpoint_id`, or an empty
+/// slice if no endpoint with that ID exists in `node`.
+fn client_clusters_for<'n>(node: &'n Node, endpoint_id: u16) -> &'n [u32] {
+    node.endpoints
+        .iter()
+        .find(|endpoint| endpoint.id == endpoint_id)
+        .map(|endpoint| endpoint.client_clusters)
+        .unwrap_or(&[])
+}
+
 struct StandardPartsMatcher;
 
 impl PartsMatcher for StandardPartsMatcher {
-    fn describe(&self, our_endpoint: EndptId, endpoint: EndptId) -> bool {
+    fn describe(&self, _node: &Node, our_endpoint: EndptId, endpoint: EndptId) -> bool {
         our_endpoint == 0 && endpoint != our_endpoint
     }
 }
@@ -64,21 +143,55 @@ impl PartsMatcher for StandardPartsMatcher {
 struct AggregatorPartsMatcher;
 
 impl PartsMatcher for AggregatorPartsMatcher {
-    fn describe(&self, our_endpoint: EndptId, endpoint: EndptId) -> bool {
+    fn describe(&self, _node: &Node, our_endpoint: EndptId, endpoint: EndptId) -> bool {
         endpoint != our_endpoint && endpoint != 0
     }
 }
 
+/// Matches endpoints composed as a tree, where each endpoint's PartsList
+/// contains exactly its transitive descendants (per `Endpoint::parent`),
+/// rather than every other endpoint on the node. This is what Matter's
+/// "tree" composition pattern (e.g. a bridge exposing bridged devices)
+/// requires.
+struct TreePartsMatcher;
+
+impl PartsMatcher for TreePartsMatcher {
+    fn describe(&self, node: &Node, our_endpoint: EndptId, endpoint: EndptId) -> bool {
+        if endpoint == our_endpoint {
+            return false;
+        }
+
+        // A well-formed ancestry chain visits each endpoint at most once, so
+        // it can never be longer than the node's endpoint count. Bound the
+        // walk by that so a malformed/cyclic `parent` chain terminates
+        // instead of spinning forever.
+        let mut current = endpoint;
+        for _ in 0..node.endpoints.len() {
+            let Some(ep) = node.endpoints.iter().find(|ep| ep.id == current) else {
+                break;
+            };
+
+            match ep.parent {
+                Some(parent) if parent == our_endpoint => return true,
+                Some(parent) => current = parent,
+                None => break,
+            }
+        }
+
+        false
+    }
+}
+
 pub trait PartsMatcher {
-    fn describe(&self, our_endpoint: EndptId, endpoint: EndptId) -> bool;
+    fn describe(&self, node: &Node, our_endpoint: EndptId, endpoint: EndptId) -> bool;
 }
 
 impl<T> PartsMatcher for &T
 where
     T: PartsMatcher,
 {
-    fn describe(&self, our_endpoint: EndptId, endpoint: EndptId) -> bool {
-        (**self).describe(our_endpoint, endpoint)
+    fn describe(&self, node: &Node, our_endpoint: EndptId, endpoint: EndptId) -> bool {
+        (**self).describe(node, our_endpoint, endpoint)
     }
 }
 
@@ -86,8 +199,8 @@ impl<T> PartsMatcher for &mut T
 where
     T: PartsMatcher,
 {
-    fn describe(&self, our_endpoint: EndptId, endpoint: EndptId) -> bool {
-        (**self).describe(our_endpoint, endpoint)
+    fn describe(&self, node: &Node, our_endpoint: EndptId, endpoint: EndptId) -> bool {
+        (**self).describe(node, our_endpoint, endpoint)
     }
 }
 
@@ -104,6 +217,10 @@ impl DescriptorCluster<'static> {
     pub fn new_aggregator(rand: Rand) -> Self {
         Self::new_matching(&AggregatorPartsMatcher, rand)
     }
+
+    pub fn new_tree(rand: Rand) -> Self {
+        Self::new_matching(&TreePartsMatcher, rand)
+    }
 }
 
 impl<'a> DescriptorCluster<'a> {
@@ -117,7 +234,25 @@ impl<'a> DescriptorCluster<'a> {
     pub fn read(&self, attr: &AttrDetails, encoder: AttrDataEncoder) -> Result<(), Error> {
         if let Some(mut writer) = encoder.with_dataver(self.data_ver.get())? {
             if attr.is_system() {
-                CLUSTER.read(attr.attr_id, writer)
+                if attr.attr_id == FEATURE_MAP.id {
+                    self.encode_feature_map(
+                        attr.node,
+                        attr.endpoint_id,
+                        AttrDataWriter::TAG,
+                        &mut writer,
+                    )?;
+                    writer.complete()
+                } else if attr.attr_id == ATTRIBUTE_LIST.id {
+                    self.encode_attribute_list(
+                        attr.node,
+                        attr.endpoint_id,
+                        AttrDataWriter::TAG,
+                        &mut writer,
+                    )?;
+                    writer.complete()
+                } else {
+                    CLUSTER.read(attr.attr_id, writer)
+                }
             } else {
                 match attr.attr_id.try_into()? {
                     Attributes::DeviceTypeList => {
@@ -156,6 +291,15 @@ impl<'a> DescriptorCluster<'a> {
                         )?;
                         writer.complete()
                     }
+                    Attributes::TagList => {
+                        self.encode_tag_list(
+                            attr.node,
+                            attr.endpoint_id,
+                            AttrDataWriter::TAG,
+                            &mut writer,
+                        )?;
+                        writer.complete()
+                    }
                 }
             }
         } else {
@@ -171,11 +315,8 @@ impl<'a> DescriptorCluster<'a> {
         tw: &mut TLVWriter,
     ) -> Result<(), Error> {
         tw.start_array(tag)?;
-        for endpoint in node.endpoints {
-            if endpoint.id == endpoint_id {
-                let dev_type = endpoint.device_type;
-                dev_type.to_tlv(tw, TagType::Anonymous)?;
-            }
+        for dev_type in device_types_for(node, endpoint_id) {
+            dev_type.to_tlv(tw, TagType::Anonymous)?;
         }
 
         tw.end_container()
@@ -210,7 +351,7 @@ impl<'a> DescriptorCluster<'a> {
         tw.start_array(tag)?;
 
         for endpoint in node.endpoints {
-            if self.matcher.describe(endpoint_id, endpoint.id) {
+            if self.matcher.describe(node, endpoint_id, endpoint.id) {
                 tw.u16(TagType::Anonymous, endpoint.id)?;
             }
         }
@@ -218,15 +359,70 @@ impl<'a> DescriptorCluster<'a> {
         tw.end_container()
     }
 
+    fn encode_feature_map(
+        &self,
+        node: &Node,
+        endpoint_id: u16,
+        tag: TagType,
+        tw: &mut TLVWriter,
+    ) -> Result<(), Error> {
+        tw.u32(tag, feature_map_for(node, endpoint_id))
+    }
+
+    /// The cluster's AttributeList, with TagList omitted for endpoints that
+    /// don't advertise the TagList feature (so AttributeList stays
+    /// consistent with the per-endpoint FeatureMap from
+    /// `encode_feature_map`).
+    fn encode_attribute_list(
+        &self,
+        node: &Node,
+        endpoint_id: u16,
+        tag: TagType,
+        tw: &mut TLVWriter,
+    ) -> Result<(), Error> {
+        tw.start_array(tag)?;
+        for attr_id in attribute_list_for(node, endpoint_id) {
+            tw.u16(TagType::Anonymous, attr_id)?;
+        }
+        tw.end_container()
+    }
+
+    /// Known gap: a direct read on an endpoint without the TagList feature
+    /// yields an empty array rather than an unsupported-attribute error.
+    fn encode_tag_list(
+        &self,
+        node: &Node,
+        endpoint_id: u16,
+        tag: TagType,
+        tw: &mut TLVWriter,
+    ) -> Result<(), Error> {
+        tw.start_array(tag)?;
+        for tag_item in tag_list_for(node, endpoint_id) {
+            tw.start_struct(TagType::Anonymous)?;
+            tag_item.mfg_code.to_tlv(tw, TagType::Context(0))?;
+            tw.u8(TagType::Context(1), tag_item.namespace_id)?;
+            tw.u8(TagType::Context(2), tag_item.tag)?;
+            if let Some(label) = tag_item.label {
+                tw.utf8(TagType::Context(3), label)?;
+            }
+            tw.end_container()?;
+        }
+
+        tw.end_container()
+    }
+
     fn encode_client_list(
         &self,
-        _node: &Node,
-        _endpoint_id: u16,
+        node: &Node,
+        endpoint_id: u16,
         tag: TagType,
         tw: &mut TLVWriter,
     ) -> Result<(), Error> {
-        // No Clients supported
         tw.start_array(tag)?;
+        for cluster_id in client_clusters_for(node, endpoint_id) {
+            tw.u32(TagType::Anonymous, *cluster_id)?;
+        }
+
         tw.end_container()
     }
 }
@@ -244,3 +440,212 @@ impl<'a> ChangeNotifier<()> for DescriptorCluster<'a> {
         self.data_ver.consume_change(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(
+        id: EndptId,
+        device_types: &'static [DeviceType],
+        tag_list: &'static [Tag<'static>],
+        client_clusters: &'static [u32],
+    ) -> Endpoint<'static> {
+        endpoint_full(id, device_types, tag_list, client_clusters, None)
+    }
+
+    fn endpoint_with_parent(
+        id: EndptId,
+        tag_list: &'static [Tag<'static>],
+        parent: Option<EndptId>,
+    ) -> Endpoint<'static> {
+        endpoint_full(id, &[], tag_list, &[], parent)
+    }
+
+    fn endpoint_full(
+        id: EndptId,
+        device_types: &'static [DeviceType],
+        tag_list: &'static [Tag<'static>],
+        client_clusters: &'static [u32],
+        parent: Option<EndptId>,
+    ) -> Endpoint<'static> {
+        Endpoint {
+            id,
+            clusters: &[],
+            device_types,
+            tag_list,
+            client_clusters,
+            parent,
+        }
+    }
+
+    #[test]
+    fn device_types_for_returns_all_types_for_matching_endpoint_only() {
+        const TYPES_0: [DeviceType; 2] = [
+            DeviceType {
+                dtype: 0x0100,
+                drev: 1,
+            },
+            DeviceType {
+                dtype: 0x0101,
+                drev: 2,
+            },
+        ];
+        const TYPES_1: [DeviceType; 1] = [DeviceType {
+            dtype: 0x0200,
+            drev: 1,
+        }];
+
+        let node = Node {
+            id: 0,
+            endpoints: &[
+                endpoint(0, &TYPES_0, &[], &[]),
+                endpoint(1, &TYPES_1, &[], &[]),
+            ],
+        };
+
+        assert_eq!(device_types_for(&node, 0), &TYPES_0);
+        assert_eq!(device_types_for(&node, 1), &TYPES_1);
+        assert!(device_types_for(&node, 2).is_empty());
+    }
+
+    #[test]
+    fn feature_map_has_tag_list_bit_only_for_endpoints_with_tags() {
+        let tag = Tag {
+            mfg_code: Nullable::some(0),
+            namespace_id: 0,
+            tag: 0,
+            label: None,
+        };
+        let node = Node {
+            id: 0,
+            endpoints: &[endpoint(0, &[], &[], &[]), endpoint(1, &[], &[tag], &[])],
+        };
+
+        assert_eq!(feature_map_for(&node, 0), 0);
+        assert_eq!(feature_map_for(&node, 1), TAG_LIST_FEATURE);
+    }
+
+    #[test]
+    fn tag_list_for_returns_tags_for_matching_endpoint_only() {
+        let tag = Tag {
+            mfg_code: Nullable::some(0),
+            namespace_id: 0,
+            tag: 0,
+            label: None,
+        };
+        let node = Node {
+            id: 0,
+            endpoints: &[endpoint(0, &[], &[], &[]), endpoint(1, &[], &[tag], &[])],
+        };
+
+        assert!(tag_list_for(&node, 0).is_empty());
+        assert_eq!(tag_list_for(&node, 1).len(), 1);
+    }
+
+    #[test]
+    fn attribute_list_omits_tag_list_for_endpoints_without_tags() {
+        let tag = Tag {
+            mfg_code: Nullable::some(0),
+            namespace_id: 0,
+            tag: 0,
+            label: None,
+        };
+        let node = Node {
+            id: 0,
+            endpoints: &[endpoint(0, &[], &[], &[]), endpoint(1, &[], &[tag], &[])],
+        };
+
+        let tag_list_id = Attributes::TagList as u16;
+        assert!(!attribute_list_for(&node, 0).any(|id| id == tag_list_id));
+        assert!(attribute_list_for(&node, 1).any(|id| id == tag_list_id));
+    }
+
+    #[test]
+    fn client_clusters_for_returns_all_ids_for_matching_endpoint_only() {
+        const CLUSTERS_0: [u32; 2] = [0x0028, 0x0029];
+        const CLUSTERS_1: [u32; 1] = [0x0006];
+
+        let node = Node {
+            id: 0,
+            endpoints: &[
+                endpoint(0, &[], &[], &CLUSTERS_0),
+                endpoint(1, &[], &[], &CLUSTERS_1),
+            ],
+        };
+
+        assert_eq!(client_clusters_for(&node, 0), &CLUSTERS_0);
+        assert_eq!(client_clusters_for(&node, 1), &CLUSTERS_1);
+        assert!(client_clusters_for(&node, 2).is_empty());
+    }
+
+    #[test]
+    fn tree_matcher_matches_direct_and_transitive_children() {
+        let node = Node {
+            id: 0,
+            endpoints: &[
+                endpoint_with_parent(0, &[], None),
+                endpoint_with_parent(1, &[], Some(0)),
+                endpoint_with_parent(2, &[], Some(1)),
+            ],
+        };
+
+        // Endpoint 1 is a direct child of 0, endpoint 2 a grandchild.
+        assert!(TreePartsMatcher.describe(&node, 0, 1));
+        assert!(TreePartsMatcher.describe(&node, 0, 2));
+        // Endpoint 2 is a child of 1, not of itself or of 0's siblings.
+        assert!(TreePartsMatcher.describe(&node, 1, 2));
+    }
+
+    #[test]
+    fn tree_matcher_ignores_siblings_and_unrelated_endpoints() {
+        let node = Node {
+            id: 0,
+            endpoints: &[
+                endpoint_with_parent(0, &[], None),
+                endpoint_with_parent(1, &[], Some(0)),
+                endpoint_with_parent(2, &[], Some(0)),
+            ],
+        };
+
+        // 1 and 2 are siblings (both children of 0), neither is the other's
+        // descendant.
+        assert!(!TreePartsMatcher.describe(&node, 1, 2));
+        assert!(!TreePartsMatcher.describe(&node, 2, 1));
+    }
+
+    #[test]
+    fn tree_matcher_rejects_self_and_endpoints_with_no_parent() {
+        let node = Node {
+            id: 0,
+            endpoints: &[
+                endpoint_with_parent(0, &[], None),
+                endpoint_with_parent(1, &[], None),
+            ],
+        };
+
+        assert!(!TreePartsMatcher.describe(&node, 0, 0));
+        // Endpoint 1 has no parent, so it is nobody's descendant.
+        assert!(!TreePartsMatcher.describe(&node, 0, 1));
+    }
+
+    #[test]
+    fn tree_matcher_terminates_on_a_cyclic_parent_chain() {
+        // Endpoints 1, 2 and 3 form a cycle (1 -> 2 -> 3 -> 1): a malformed
+        // configuration that must not hang the matcher. Endpoint 0 sits
+        // outside the cycle entirely, so walking the chain from any of them
+        // must terminate and report "not a descendant" rather than loop
+        // forever or falsely match.
+        let node = Node {
+            id: 0,
+            endpoints: &[
+                endpoint_with_parent(0, &[], None),
+                endpoint_with_parent(1, &[], Some(2)),
+                endpoint_with_parent(2, &[], Some(3)),
+                endpoint_with_parent(3, &[], Some(1)),
+            ],
+        };
+
+        assert!(!TreePartsMatcher.describe(&node, 0, 1));
+    }
+}